@@ -1,26 +1,89 @@
 use nannou::prelude::*;
 use nannou_audio as audio;
 use nannou_audio::Buffer;
+use std::collections::VecDeque;
 use std::f64::consts::PI;
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
 
 const CELL_COUNT: usize = 128;
+const MIN_LENGTH: usize = 8;
+const LENGTH_STEP: usize = 2;
+const PICKUP_STEP: f64 = 1.0;
+const EQ_FREQ_STEP: f64 = 50.0;
+const EQ_Q_STEP: f64 = 0.1;
+const EQ_GAIN_STEP: f64 = 1.0;
+
+// How many samples the producer simulates per batch, and how many sample
+// slots the queue between producer and audio callback can hold. Bigger
+// batches mean the simulation thread can fall behind the audio thread
+// for longer before the callback runs dry.
+const BATCH_SAMPLES: usize = 512;
+const QUEUE_CAPACITY_SAMPLES: usize = BATCH_SAMPLES * 8;
+
+// Courant number = wave_speed * dt / dx. Must stay <= 1 for stability;
+// C^2 is the stiffness coefficient in the interior update below, and C
+// itself shows up again in the Mur absorbing boundary.
+const C: f64 = 0.707_106_781_186_547_5;
+
+// The excitation's ADSR and FM math is all phase-per-sample, so it needs
+// the output sample rate. cpal reports the real one at stream build time,
+// but by then the producer thread is already running; assume the common
+// default rather than threading it through a message.
+const SAMPLE_RATE: f64 = 44_100.0;
+
+// Cell the continuous (Bowed) drive is injected into, and the cell a
+// pluck's triangular span is centered on, both measured from the start
+// of the tube.
+const DRIVE_CELL: usize = CELL_COUNT / 4;
+const PLUCK_CELL: usize = CELL_COUNT / 4;
+
 fn main() {
     nannou::app(model).update(update).run();
 }
 
 struct Model {
     stream: audio::Stream<Audio>,
-    chamber: Chamber<CELL_COUNT>,
+    producer_tx: Sender<ProducerMsg>,
+    snapshot: Arc<Mutex<[f64; CELL_COUNT]>>,
+    view_cells: [f64; CELL_COUNT],
+    eq_snapshot: Arc<Mutex<[BandParams; 3]>>,
 }
 impl Model {
     fn reset(&mut self) {
-        self.chamber.cells.cur = [0.0; CELL_COUNT];
-        self.chamber.cells.prev = [0.0; CELL_COUNT];
+        self.producer_tx.send(ProducerMsg::Reset).unwrap();
+    }
+}
+
+// How each end of the active tube behaves when the wave reaches it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BoundaryCondition {
+    // Closed end: u_next = 0, total reflection with inverted phase.
+    Fixed,
+    // Pressure-release / antinode end: u_next[edge] = u_next[neighbor].
+    Open,
+    // First-order Mur radiation condition: outgoing waves leave without
+    // reflecting back into the tube.
+    Absorbing,
+}
+impl BoundaryCondition {
+    fn cycle(self) -> Self {
+        match self {
+            BoundaryCondition::Fixed => BoundaryCondition::Open,
+            BoundaryCondition::Open => BoundaryCondition::Absorbing,
+            BoundaryCondition::Absorbing => BoundaryCondition::Fixed,
+        }
     }
 }
 
 struct Chamber<const N: usize> {
     cells: Cells<N>,
+    // Active portion of the tube; cells beyond this stay at rest. Arrow
+    // keys grow/shrink this to change the chamber's resonant length.
+    length: usize,
+    left_boundary: BoundaryCondition,
+    right_boundary: BoundaryCondition,
 }
 struct Cells<const N: usize> {
     prev: [f64; N],
@@ -39,6 +102,9 @@ impl<const N: usize> Chamber<N> {
     fn new() -> Self {
         Self {
             cells: Cells::new(),
+            length: N,
+            left_boundary: BoundaryCondition::Fixed,
+            right_boundary: BoundaryCondition::Fixed,
         }
     }
 
@@ -46,6 +112,29 @@ impl<const N: usize> Chamber<N> {
         self.cells.cur[0] += pressure;
         // self.cells[0] += pressure;
     }
+
+    // Adds a continuous driving source at `index`, for the Bowed
+    // excitation mode's per-sample oscillator.
+    fn inject(&mut self, index: usize, amount: f64) {
+        if index > 0 && index < self.length - 1 {
+            self.cells.cur[index] += amount;
+        }
+    }
+
+    // Displaces a triangular span of cells centered on `center` and
+    // zeroes their velocity, the initial condition for a plucked string
+    // rather than an impulse driven in from rest.
+    fn pluck(&mut self, center: usize, amplitude: f64) {
+        let span = (self.length / 8).max(2);
+        let start = center.saturating_sub(span).max(1);
+        let end = (center + span).min(self.length - 2);
+        for i in start..=end {
+            let dist = (i as isize - center as isize).unsigned_abs() as f64;
+            let triangle = (1.0 - dist / span as f64).max(0.0);
+            self.cells.cur[i] = amplitude * triangle;
+            self.cells.prev[i] = self.cells.cur[i];
+        }
+    }
     // Given:
     // - N: number of cells
     // - u_prev[N]: pressure values at previous time step (t-1)
@@ -73,12 +162,13 @@ impl<const N: usize> Chamber<N> {
 
     fn update_pressures(&mut self) {
         let mut next = [0.0; N];
-        for index in 1..(N - 1) {
+        let n = self.length;
+        for index in 1..(n - 1) {
             let cur = self.cells.cur[index];
             let prev = self.cells.prev[index];
             let left = self.cells.cur[index - 1];
             let right = self.cells.cur[index + 1];
-            next[index] = (2.0 * cur) - prev + 0.5 * (right - 2.0 * cur + left);
+            next[index] = (2.0 * cur) - prev + C * C * (right - 2.0 * cur + left);
         }
         // for (index, next_value) in next.iter_mut().enumerate() {
         //     let cur = self.cells.cur[index];
@@ -95,14 +185,644 @@ impl<const N: usize> Chamber<N> {
         //     };
         //     *next_value = (2.0 * cur) - prev + 0.1 * (right - 2.0 * cur + left);
         // }
+
+        // The Absorbing case reads next[1] / next[n - 2], so the
+        // boundary update has to come after the interior loop above.
+        let cur = &self.cells.cur;
+        next[0] = match self.left_boundary {
+            BoundaryCondition::Fixed => 0.0,
+            BoundaryCondition::Open => next[1],
+            BoundaryCondition::Absorbing => {
+                cur[1] + ((C - 1.0) / (C + 1.0)) * (next[1] - cur[0])
+            }
+        };
+        next[n - 1] = match self.right_boundary {
+            BoundaryCondition::Fixed => 0.0,
+            BoundaryCondition::Open => next[n - 2],
+            BoundaryCondition::Absorbing => {
+                cur[n - 2] + ((C - 1.0) / (C + 1.0)) * (next[n - 2] - cur[n - 1])
+            }
+        };
+
         self.cells.prev = self.cells.cur;
         self.cells.cur = next;
     }
 }
 
+enum EnvelopeStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+// Classic ADSR: key-down starts the attack, key-up starts the release,
+// and everything in between is tracked in samples so `advance` can be
+// called once per sample from the producer loop.
+struct Envelope {
+    attack_samples: u64,
+    decay_samples: u64,
+    sustain_level: f64,
+    release_samples: u64,
+    stage: EnvelopeStage,
+    elapsed: u64,
+    level: f64,
+    release_start_level: f64,
+}
+impl Envelope {
+    fn new(attack_samples: u64, decay_samples: u64, sustain_level: f64, release_samples: u64) -> Self {
+        Self {
+            attack_samples,
+            decay_samples,
+            sustain_level,
+            release_samples,
+            stage: EnvelopeStage::Idle,
+            elapsed: 0,
+            level: 0.0,
+            release_start_level: 0.0,
+        }
+    }
+
+    fn note_on(&mut self) {
+        self.stage = EnvelopeStage::Attack;
+        self.elapsed = 0;
+    }
+
+    fn note_off(&mut self) {
+        self.release_start_level = self.level;
+        self.stage = EnvelopeStage::Release;
+        self.elapsed = 0;
+    }
+
+    fn advance(&mut self) -> f64 {
+        match self.stage {
+            EnvelopeStage::Idle => self.level = 0.0,
+            EnvelopeStage::Attack => {
+                self.level = if self.attack_samples == 0 {
+                    1.0
+                } else {
+                    self.elapsed as f64 / self.attack_samples as f64
+                };
+                self.elapsed += 1;
+                if self.elapsed >= self.attack_samples {
+                    self.stage = EnvelopeStage::Decay;
+                    self.elapsed = 0;
+                }
+            }
+            EnvelopeStage::Decay => {
+                let t = if self.decay_samples == 0 {
+                    1.0
+                } else {
+                    self.elapsed as f64 / self.decay_samples as f64
+                };
+                self.level = 1.0 - t * (1.0 - self.sustain_level);
+                self.elapsed += 1;
+                if self.elapsed >= self.decay_samples {
+                    self.stage = EnvelopeStage::Sustain;
+                    self.level = self.sustain_level;
+                }
+            }
+            EnvelopeStage::Sustain => self.level = self.sustain_level,
+            EnvelopeStage::Release => {
+                let t = if self.release_samples == 0 {
+                    1.0
+                } else {
+                    self.elapsed as f64 / self.release_samples as f64
+                };
+                self.level = self.release_start_level * (1.0 - t).max(0.0);
+                self.elapsed += 1;
+                if self.elapsed >= self.release_samples {
+                    self.stage = EnvelopeStage::Idle;
+                    self.level = 0.0;
+                }
+            }
+        }
+        self.level
+    }
+}
+
+// How a key-down injects energy into the chamber.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ExcitationMode {
+    // A short impulse, like the original single-sample `add_pressure`.
+    Struck,
+    // An initial triangular displacement across a span of cells, like
+    // plucking a string from rest.
+    Plucked,
+    // A sustained, ADSR-enveloped oscillator, optionally FM'd by a
+    // second sine.
+    Bowed,
+}
+
+// The driving source injected into the chamber each sim step. Only
+// `Bowed` runs every sample; `Struck`/`Plucked` act once on key-down.
+struct Excitation {
+    mode: ExcitationMode,
+    envelope: Envelope,
+    drive_phase: f64,
+    fm_phase: f64,
+    f_carrier: f64,
+    fm_amp: f64,
+    fm_freq: f64,
+}
+impl Excitation {
+    fn new() -> Self {
+        Self {
+            mode: ExcitationMode::Struck,
+            envelope: Envelope::new(
+                (0.01 * SAMPLE_RATE) as u64,
+                (0.05 * SAMPLE_RATE) as u64,
+                0.6,
+                (0.3 * SAMPLE_RATE) as u64,
+            ),
+            drive_phase: 0.0,
+            fm_phase: 0.0,
+            f_carrier: 220.0,
+            fm_amp: 0.0,
+            fm_freq: 5.0,
+        }
+    }
+
+    fn key_down(&mut self, chamber: &mut Chamber<CELL_COUNT>) {
+        match self.mode {
+            ExcitationMode::Struck => chamber.add_pressure(0.1),
+            ExcitationMode::Plucked => chamber.pluck(PLUCK_CELL, 0.3),
+            ExcitationMode::Bowed => self.envelope.note_on(),
+        }
+    }
+
+    fn key_up(&mut self) {
+        if self.mode == ExcitationMode::Bowed {
+            self.envelope.note_off();
+        }
+    }
+
+    // Advances the continuous driving oscillator by one sample and
+    // returns the amount to inject into the chamber this step. Zero for
+    // the one-shot modes, since their impulse was already applied by
+    // `key_down`.
+    fn next_drive(&mut self) -> f64 {
+        if self.mode != ExcitationMode::Bowed {
+            return 0.0;
+        }
+        let level = self.envelope.advance();
+        if level <= 0.0 {
+            return 0.0;
+        }
+        let drive = (2.0 * PI * self.drive_phase).sin();
+        let instantaneous_hz = self.f_carrier + self.fm_amp * (2.0 * PI * self.fm_phase).sin();
+        self.drive_phase += instantaneous_hz / SAMPLE_RATE;
+        self.fm_phase += self.fm_freq / SAMPLE_RATE;
+        drive * level * 0.1
+    }
+}
+
+// Output is a stereo pair of virtual cardioid microphones; every
+// `AudioFrame` interleaves `STEREO_CHANNELS` samples per simulated frame.
+const STEREO_CHANNELS: usize = 2;
+
+// Length of the pickup's Doppler delay line, and the delay behind the
+// write head the fractional read pointer starts at. It drifts from there
+// as the pickup moves and is clamped to [1, PICKUP_RING_LEN - 2].
+const PICKUP_RING_LEN: usize = 512;
+const PICKUP_BASE_DELAY: f64 = 64.0;
+
+// Cardioid pattern factor (p in `w = p + (1-p)*cos(theta)`) and each
+// virtual mic capsule's angle off center (the two are 2 * EAR_SPREAD
+// apart).
+const MIC_PATTERN_P: f64 = 0.5;
+const EAR_SPREAD: f64 = PI / 3.0;
+
+// A single movable pickup along the tube. Reads the chamber's pressure
+// at its (possibly fractional) cell, runs it through a short delay ring,
+// and reads that ring back at a fractional, Doppler-scaled offset so
+// moving the pickup bends the pitch the way a moving microphone would.
+struct Pickup {
+    cell: f64,
+    prev_cell: f64,
+    ring: [f64; PICKUP_RING_LEN],
+    write_index: usize,
+    read_offset: f64,
+}
+impl Pickup {
+    fn new(cell: f64) -> Self {
+        Self {
+            cell,
+            prev_cell: cell,
+            ring: [0.0; PICKUP_RING_LEN],
+            write_index: 0,
+            read_offset: PICKUP_BASE_DELAY,
+        }
+    }
+
+    fn normalized_position(&self, length: usize) -> f64 {
+        let span = (length.max(2) - 1) as f64;
+        (self.cell / span) * 2.0 - 1.0
+    }
+
+    fn sample<const N: usize>(&mut self, chamber: &Chamber<N>) -> f64 {
+        let index = self.cell.clamp(0.0, (chamber.length - 1) as f64);
+        let lo = index.floor() as usize;
+        let hi = (lo + 1).min(chamber.length - 1);
+        let frac = index - lo as f64;
+        let raw = chamber.cells.cur[lo] * (1.0 - frac) + chamber.cells.cur[hi] * frac;
+
+        self.ring[self.write_index] = raw;
+        self.write_index = (self.write_index + 1) % PICKUP_RING_LEN;
+
+        // v is the pickup's own cell-velocity, in cells/sample; c is the
+        // wave speed implied by the Courant number C (dx = 1 cell, dt =
+        // 1 sample). rate < 1 while the pickup moves toward higher cells
+        // (v > 0, lower pitch) and > 1 while it moves toward lower cells
+        // with -C < v < 0 (higher pitch). For v < -C, such as a whole-cell
+        // step toward lower cells within one sample, rate is negative and
+        // the read pointer steps backwards.
+        let velocity = self.cell - self.prev_cell;
+        self.prev_cell = self.cell;
+        let rate = C / (C + velocity);
+        self.read_offset = (self.read_offset + 1.0 - rate).clamp(1.0, (PICKUP_RING_LEN - 2) as f64);
+
+        let read_pos = (self.write_index as f64 - self.read_offset).rem_euclid(PICKUP_RING_LEN as f64);
+        let r_lo = read_pos.floor() as usize % PICKUP_RING_LEN;
+        let r_hi = (r_lo + 1) % PICKUP_RING_LEN;
+        let r_frac = read_pos - read_pos.floor();
+        self.ring[r_lo] * (1.0 - r_frac) + self.ring[r_hi] * r_frac
+    }
+}
+
+// Cardioid weights for the left/right virtual mic capsules, given the
+// source's normalized position x in [-1, 1] (theta is the angle from
+// each capsule's forward/"ear" direction).
+fn cardioid_weights(x: f64) -> (f64, f64) {
+    let source_angle = x * (PI / 2.0);
+    let theta_l = source_angle + EAR_SPREAD;
+    let theta_r = source_angle - EAR_SPREAD;
+    let w_l = MIC_PATTERN_P + (1.0 - MIC_PATTERN_P) * theta_l.cos();
+    let w_r = MIC_PATTERN_P + (1.0 - MIC_PATTERN_P) * theta_r.cos();
+    (w_l, w_r)
+}
+
+// One batch of simulated samples, timestamped by the producer's running
+// sample clock so the consumer can tell how far ahead it's buffered.
+// Samples are interleaved `STEREO_CHANNELS`-wide frames, not raw mono.
+struct AudioFrame {
+    clock: u64,
+    samples: Vec<f32>,
+}
+
+// A bounded, single-producer-single-consumer queue of `AudioFrame`s. The
+// audio callback drains it with `pop_next`/`unpop`; the simulation thread
+// fills it with `push`, using `space_available` to size its next batch.
+struct ClockedQueue<T> {
+    queue: Mutex<VecDeque<T>>,
+    capacity: usize,
+}
+
+impl ClockedQueue<AudioFrame> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            queue: Mutex::new(VecDeque::new()),
+            capacity,
+        }
+    }
+
+    fn queued_samples(&self) -> usize {
+        self.queue.lock().unwrap().iter().map(|f| f.samples.len()).sum()
+    }
+
+    fn push(&self, frame: AudioFrame) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        let queued: usize = queue.iter().map(|f| f.samples.len()).sum();
+        if queued + frame.samples.len() > self.capacity {
+            return false;
+        }
+        queue.push_back(frame);
+        true
+    }
+
+    fn pop_next(&self) -> Option<AudioFrame> {
+        self.queue.lock().unwrap().pop_front()
+    }
+
+    // Hand a partially-consumed frame back to the front of the queue so
+    // the next callback picks up exactly where this one left off.
+    fn unpop(&self, frame: AudioFrame) {
+        self.queue.lock().unwrap().push_front(frame);
+    }
+
+    fn peek_clock(&self) -> Option<u64> {
+        self.queue.lock().unwrap().front().map(|f| f.clock)
+    }
+
+    // Free slots the producer can still fill, in units of *frames*
+    // rather than raw samples. The classic bug here is treating mono
+    // sample slots as if they were already per-channel; dividing by
+    // `channels` is what keeps the stereo producer from overfilling a
+    // queue whose capacity is counted in samples.
+    fn space_available(&self, channels: usize) -> usize {
+        let free = self.capacity.saturating_sub(self.queued_samples());
+        free / channels.max(1)
+    }
+}
+
+enum ProducerMsg {
+    Reset,
+    AdjustLength(i64),
+    CycleLeftBoundary,
+    CycleRightBoundary,
+    ExcitationKeyDown,
+    ExcitationKeyUp,
+    SetExcitationMode(ExcitationMode),
+    AdjustFmAmp(f64),
+    AdjustFmFreq(f64),
+    MovePickup(f64),
+}
+
+// Runs the chamber simulation on its own thread, decoupled from the audio
+// callback's real-time deadline. It tops the queue up in batches and only
+// blocks when the queue is full, until the callback asks for more.
+fn spawn_producer(
+    queue: Arc<ClockedQueue<AudioFrame>>,
+    needs_more: Arc<(Mutex<bool>, Condvar)>,
+    snapshot: Arc<Mutex<[f64; CELL_COUNT]>>,
+) -> Sender<ProducerMsg> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut chamber = Chamber::<CELL_COUNT>::new();
+        let mut excitation = Excitation::new();
+        let mut pickup = Pickup::new((CELL_COUNT / 2) as f64);
+        let mut clock = 0u64;
+        loop {
+            loop {
+                match rx.try_recv() {
+                    Ok(ProducerMsg::Reset) => {
+                        chamber.cells.cur = [0.0; CELL_COUNT];
+                        chamber.cells.prev = [0.0; CELL_COUNT];
+                    }
+                    Ok(ProducerMsg::AdjustLength(delta)) => {
+                        let length = (chamber.length as i64 + delta)
+                            .clamp(MIN_LENGTH as i64, CELL_COUNT as i64);
+                        chamber.length = length as usize;
+                    }
+                    Ok(ProducerMsg::CycleLeftBoundary) => {
+                        chamber.left_boundary = chamber.left_boundary.cycle();
+                    }
+                    Ok(ProducerMsg::CycleRightBoundary) => {
+                        chamber.right_boundary = chamber.right_boundary.cycle();
+                    }
+                    Ok(ProducerMsg::ExcitationKeyDown) => excitation.key_down(&mut chamber),
+                    Ok(ProducerMsg::ExcitationKeyUp) => excitation.key_up(),
+                    Ok(ProducerMsg::SetExcitationMode(mode)) => excitation.mode = mode,
+                    Ok(ProducerMsg::AdjustFmAmp(delta)) => {
+                        excitation.fm_amp = (excitation.fm_amp + delta).max(0.0);
+                    }
+                    Ok(ProducerMsg::AdjustFmFreq(delta)) => {
+                        excitation.fm_freq = (excitation.fm_freq + delta).max(0.1);
+                    }
+                    Ok(ProducerMsg::MovePickup(delta)) => {
+                        pickup.cell = (pickup.cell + delta).clamp(0.0, (chamber.length - 1) as f64);
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+
+            let available = queue.space_available(STEREO_CHANNELS);
+            if available == 0 {
+                let (lock, cvar) = &*needs_more;
+                let mut need = lock.lock().unwrap();
+                while !*need {
+                    need = cvar.wait(need).unwrap();
+                }
+                *need = false;
+                continue;
+            }
+
+            let batch_len = available.min(BATCH_SAMPLES);
+            let mut samples = Vec::with_capacity(batch_len * STEREO_CHANNELS);
+            for _ in 0..batch_len {
+                let drive = excitation.next_drive();
+                if drive != 0.0 {
+                    chamber.inject(DRIVE_CELL, drive);
+                }
+                chamber.update_pressures();
+
+                let raw = pickup.sample(&chamber);
+                let (w_l, w_r) = cardioid_weights(pickup.normalized_position(chamber.length));
+                samples.push((raw * w_l) as f32);
+                samples.push((raw * w_r) as f32);
+            }
+            if let Ok(mut snapshot) = snapshot.lock() {
+                *snapshot = chamber.cells.cur;
+            }
+            let frame = AudioFrame { clock, samples };
+            clock += batch_len as u64;
+            queue.push(frame);
+        }
+    });
+    tx
+}
+
+// RBJ-cookbook biquad shapes available in the post-processing chain.
+#[derive(Clone, Copy, PartialEq)]
+enum FilterKind {
+    LowPass,
+    HighPass,
+    Bell,
+}
+
+// The knobs for one band, independent of any running filter state. Kept
+// separate from `Biquad` so the UI thread can snapshot and plot a band's
+// response without touching the audio thread's per-sample history.
+#[derive(Clone, Copy)]
+struct BandParams {
+    kind: FilterKind,
+    f0: f64,
+    q: f64,
+    gain_db: f64,
+}
+
+// RBJ Audio Cookbook coefficients, normalized by a0. `gain_db` is only
+// meaningful for `Bell`; the low/high pass shapes ignore it.
+fn rbj_coeffs(params: BandParams, sample_rate: f64) -> (f64, f64, f64, f64, f64) {
+    let omega = 2.0 * PI * params.f0 / sample_rate;
+    let (sin_w, cos_w) = (omega.sin(), omega.cos());
+    let alpha = sin_w / (2.0 * params.q);
+    let (b0, b1, b2, a0, a1, a2) = match params.kind {
+        FilterKind::LowPass => {
+            let b1 = 1.0 - cos_w;
+            (b1 / 2.0, b1, b1 / 2.0, 1.0 + alpha, -2.0 * cos_w, 1.0 - alpha)
+        }
+        FilterKind::HighPass => {
+            let b0 = (1.0 + cos_w) / 2.0;
+            (b0, -(1.0 + cos_w), b0, 1.0 + alpha, -2.0 * cos_w, 1.0 - alpha)
+        }
+        FilterKind::Bell => {
+            let a = 10f64.powf(params.gain_db / 40.0);
+            (
+                1.0 + alpha * a,
+                -2.0 * cos_w,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos_w,
+                1.0 - alpha / a,
+            )
+        }
+    };
+    (b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0)
+}
+
+// |H(e^{jw})| for a normalized biquad, evaluated directly from its
+// coefficients rather than by running it — used to plot the response
+// curve.
+fn biquad_magnitude(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64, freq_hz: f64, sample_rate: f64) -> f64 {
+    let omega = 2.0 * PI * freq_hz / sample_rate;
+    let (cos1, sin1) = (omega.cos(), omega.sin());
+    let (cos2, sin2) = ((2.0 * omega).cos(), (2.0 * omega).sin());
+    let num_re = b0 + b1 * cos1 + b2 * cos2;
+    let num_im = -b1 * sin1 - b2 * sin2;
+    let den_re = 1.0 + a1 * cos1 + a2 * cos2;
+    let den_im = -a1 * sin1 - a2 * sin2;
+    (num_re * num_re + num_im * num_im).sqrt() / (den_re * den_re + den_im * den_im).sqrt()
+}
+
+// Direct-Form-I biquad: a band's left and right channels each get their
+// own instance with the same coefficients, so each channel keeps its own
+// two-sample history of inputs and outputs.
+#[derive(Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+impl Biquad {
+    fn set_coeffs(&mut self, b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) {
+        self.b0 = b0;
+        self.b1 = b1;
+        self.b2 = b2;
+        self.a1 = a1;
+        self.a2 = a2;
+    }
+
+    fn process(&mut self, x0: f64) -> f64 {
+        let y0 =
+            self.b0 * x0 + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x0;
+        self.y2 = self.y1;
+        self.y1 = y0;
+        y0
+    }
+}
+
+// One EQ band: shared RBJ parameters driving a pair of independent,
+// per-channel Direct-Form-I biquads.
+struct Band {
+    params: BandParams,
+    left: Biquad,
+    right: Biquad,
+}
+impl Band {
+    fn new(kind: FilterKind, f0: f64, q: f64, gain_db: f64) -> Self {
+        let mut band = Self {
+            params: BandParams { kind, f0, q, gain_db },
+            left: Biquad::default(),
+            right: Biquad::default(),
+        };
+        band.recompute();
+        band
+    }
+
+    fn recompute(&mut self) {
+        let (b0, b1, b2, a1, a2) = rbj_coeffs(self.params, SAMPLE_RATE);
+        self.left.set_coeffs(b0, b1, b2, a1, a2);
+        self.right.set_coeffs(b0, b1, b2, a1, a2);
+    }
+
+    fn process(&mut self, left: f64, right: f64) -> (f64, f64) {
+        (self.left.process(left), self.right.process(right))
+    }
+}
+
+// Low-pass -> high-pass -> bell, in series, each nudgeable in f0/Q/gain
+// via the currently-selected active band.
+struct EqChain {
+    bands: [Band; 3],
+    active: usize,
+}
+impl EqChain {
+    fn new() -> Self {
+        Self {
+            bands: [
+                Band::new(FilterKind::LowPass, 4_000.0, 0.707, 0.0),
+                Band::new(FilterKind::HighPass, 80.0, 0.707, 0.0),
+                Band::new(FilterKind::Bell, 1_000.0, 1.0, 0.0),
+            ],
+            active: 0,
+        }
+    }
+
+    fn process(&mut self, left: f64, right: f64) -> (f64, f64) {
+        self.bands.iter_mut().fold((left, right), |(l, r), band| band.process(l, r))
+    }
+
+    fn params(&self) -> [BandParams; 3] {
+        [self.bands[0].params, self.bands[1].params, self.bands[2].params]
+    }
+
+    fn cycle_active(&mut self) {
+        self.active = (self.active + 1) % self.bands.len();
+    }
+
+    fn nudge_f0(&mut self, delta: f64) {
+        let band = &mut self.bands[self.active];
+        band.params.f0 = (band.params.f0 + delta).clamp(20.0, 20_000.0);
+        band.recompute();
+    }
+
+    fn nudge_q(&mut self, delta: f64) {
+        let band = &mut self.bands[self.active];
+        band.params.q = (band.params.q + delta).clamp(0.1, 20.0);
+        band.recompute();
+    }
+
+    fn nudge_gain(&mut self, delta: f64) {
+        let band = &mut self.bands[self.active];
+        band.params.gain_db = (band.params.gain_db + delta).clamp(-24.0, 24.0);
+        band.recompute();
+    }
+}
+
+// Lives on the audio thread. It no longer simulates anything itself; it
+// just drains pre-computed frames from the shared queue, runs them
+// through the EQ chain, and asks the producer for more whenever the
+// queue is empty or has free space.
 struct Audio {
-    phase: f64,
-    hz: f64,
+    queue: Arc<ClockedQueue<AudioFrame>>,
+    needs_more: Arc<(Mutex<bool>, Condvar)>,
+    volume: f32,
+    eq: EqChain,
+    eq_snapshot: Arc<Mutex<[BandParams; 3]>>,
+}
+impl Audio {
+    fn request_more(&self) {
+        let (lock, cvar) = &*self.needs_more;
+        *lock.lock().unwrap() = true;
+        cvar.notify_one();
+    }
+
+    fn publish_eq(&self) {
+        if let Ok(mut snapshot) = self.eq_snapshot.lock() {
+            *snapshot = self.eq.params();
+        }
+    }
 }
 
 fn model(app: &App) -> Model {
@@ -110,18 +830,29 @@ fn model(app: &App) -> Model {
     let window = app
         .new_window()
         .key_pressed(key_pressed)
+        .key_released(key_released)
         .view(view)
         .build()
         .unwrap();
 
     // Initialise the audio API so we can spawn an audio stream.
     let audio_host = audio::Host::new();
-    let chamber = Chamber::new();
+    let snapshot = Arc::new(Mutex::new([0.0; CELL_COUNT]));
+    let queue = Arc::new(ClockedQueue::new(QUEUE_CAPACITY_SAMPLES));
+    let needs_more = Arc::new((Mutex::new(true), Condvar::new()));
+
+    let producer_tx = spawn_producer(queue.clone(), needs_more.clone(), snapshot.clone());
+
+    let eq = EqChain::new();
+    let eq_snapshot = Arc::new(Mutex::new(eq.params()));
 
     // Initialise the state that we want to live on the audio thread.
     let model = Audio {
-        phase: 0.0,
-        hz: 440.0,
+        queue,
+        needs_more,
+        volume: 0.3,
+        eq,
+        eq_snapshot: eq_snapshot.clone(),
     };
 
     let stream = audio_host
@@ -132,21 +863,67 @@ fn model(app: &App) -> Model {
 
     // stream.play().unwrap();
 
-    Model { stream, chamber }
+    Model {
+        stream,
+        producer_tx,
+        snapshot,
+        view_cells: [0.0; CELL_COUNT],
+        eq_snapshot,
+    }
 }
 
-// A function that renders the given `Audio` to the given `Buffer`.
-// In this case we play a simple sine wave at the audio's current frequency in `hz`.
+// Drains stereo frames from the clocked queue into the output buffer,
+// running each through the EQ chain. A frame that doesn't fully fit is
+// split and handed back with `unpop` so the next callback resumes
+// mid-frame; an empty queue fills with silence and wakes the producer.
 fn audio(audio: &mut Audio, buffer: &mut Buffer) {
-    let sample_rate = buffer.sample_rate() as f64;
-    let volume = 0.5;
-    for frame in buffer.frames_mut() {
-        let sine_amp = (2.0 * PI * audio.phase).sin() as f32;
-        audio.phase += audio.hz / sample_rate;
-        audio.phase %= sample_rate;
-        for channel in frame {
-            *channel = sine_amp * volume;
+    let channels = buffer.channels().max(1);
+    let mut frames = buffer.frames_mut();
+    let mut frame = audio.queue.pop_next();
+    let mut pos = 0usize;
+
+    while let Some(out_frame) = frames.next() {
+        while matches!(&frame, Some(f) if pos + STEREO_CHANNELS > f.samples.len()) {
+            frame = audio.queue.pop_next();
+            pos = 0;
+        }
+        let (left, right) = match &frame {
+            Some(f) => {
+                let left = f.samples[pos] * audio.volume;
+                let right = f.samples[pos + 1] * audio.volume;
+                pos += STEREO_CHANNELS;
+                (left, right)
+            }
+            None => (0.0, 0.0),
+        };
+        let (left, right) = audio.eq.process(left as f64, right as f64);
+        let (left, right) = (left as f32, right as f32);
+        if channels >= 2 {
+            out_frame[0] = left;
+            out_frame[1] = right;
+            for channel in out_frame.iter_mut().skip(2) {
+                *channel = 0.0;
+            }
+        } else {
+            for channel in out_frame.iter_mut() {
+                *channel = 0.5 * (left + right);
+            }
+        }
+    }
+
+    match frame {
+        Some(mut f) if pos < f.samples.len() => {
+            let remainder = AudioFrame {
+                clock: f.clock + (pos / STEREO_CHANNELS) as u64,
+                samples: f.samples.split_off(pos),
+            };
+            audio.queue.unpop(remainder);
         }
+        _ => {}
+    }
+
+    if audio.queue.peek_clock().is_none() || audio.queue.space_available(STEREO_CHANNELS) > 0 {
+        audio.request_more();
     }
 }
 
@@ -155,8 +932,10 @@ fn key_pressed(_app: &App, model: &mut Model, key: Key) {
         Key::R => {
             model.reset();
         }
+        // Strike, pluck, or start bowing the chamber depending on the
+        // active excitation mode.
         Key::A => {
-            model.chamber.add_pressure(0.1);
+            model.producer_tx.send(ProducerMsg::ExcitationKeyDown).unwrap();
         }
         // Pause or unpause the audio when Space is pressed.
         Key::Space => {
@@ -166,21 +945,137 @@ fn key_pressed(_app: &App, model: &mut Model, key: Key) {
                 model.stream.play().unwrap();
             }
         }
-        // Raise the frequency when the up key is pressed.
+        // Lengthen the tube when the up key is pressed, raising its
+        // fundamental's period (lower pitch).
         Key::Up => {
+            model
+                .producer_tx
+                .send(ProducerMsg::AdjustLength(LENGTH_STEP as i64))
+                .unwrap();
+        }
+        // Shorten the tube when the down key is pressed (higher pitch).
+        Key::Down => {
+            model
+                .producer_tx
+                .send(ProducerMsg::AdjustLength(-(LENGTH_STEP as i64)))
+                .unwrap();
+        }
+        // Cycle the left/right end's boundary condition: Fixed -> Open
+        // -> Absorbing -> Fixed.
+        Key::LBracket => {
+            model.producer_tx.send(ProducerMsg::CycleLeftBoundary).unwrap();
+        }
+        Key::RBracket => {
+            model.producer_tx.send(ProducerMsg::CycleRightBoundary).unwrap();
+        }
+        // Select the excitation mode.
+        Key::Key1 => {
+            model
+                .producer_tx
+                .send(ProducerMsg::SetExcitationMode(ExcitationMode::Struck))
+                .unwrap();
+        }
+        Key::Key2 => {
+            model
+                .producer_tx
+                .send(ProducerMsg::SetExcitationMode(ExcitationMode::Plucked))
+                .unwrap();
+        }
+        Key::Key3 => {
+            model
+                .producer_tx
+                .send(ProducerMsg::SetExcitationMode(ExcitationMode::Bowed))
+                .unwrap();
+        }
+        // Z/X nudge the FM depth, C/V nudge the FM rate.
+        Key::Z => {
+            model.producer_tx.send(ProducerMsg::AdjustFmAmp(-5.0)).unwrap();
+        }
+        Key::X => {
+            model.producer_tx.send(ProducerMsg::AdjustFmAmp(5.0)).unwrap();
+        }
+        Key::C => {
+            model.producer_tx.send(ProducerMsg::AdjustFmFreq(-0.5)).unwrap();
+        }
+        Key::V => {
+            model.producer_tx.send(ProducerMsg::AdjustFmFreq(0.5)).unwrap();
+        }
+        // Slide the stereo pickup along the tube; panning and Doppler
+        // bend follow its motion.
+        Key::Left => {
+            model
+                .producer_tx
+                .send(ProducerMsg::MovePickup(-PICKUP_STEP))
+                .unwrap();
+        }
+        Key::Right => {
+            model.producer_tx.send(ProducerMsg::MovePickup(PICKUP_STEP)).unwrap();
+        }
+        // Cycle which EQ band (low-pass, high-pass, bell) the following
+        // keys nudge.
+        Key::Key4 => {
             model
                 .stream
                 .send(|audio| {
-                    audio.hz += 10.0;
+                    audio.eq.cycle_active();
+                    audio.publish_eq();
                 })
                 .unwrap();
         }
-        // Lower the frequency when the down key is pressed.
-        Key::Down => {
+        // Minus/Equals nudge the active band's center frequency.
+        Key::Minus => {
             model
                 .stream
                 .send(|audio| {
-                    audio.hz -= 10.0;
+                    audio.eq.nudge_f0(-EQ_FREQ_STEP);
+                    audio.publish_eq();
+                })
+                .unwrap();
+        }
+        Key::Equals => {
+            model
+                .stream
+                .send(|audio| {
+                    audio.eq.nudge_f0(EQ_FREQ_STEP);
+                    audio.publish_eq();
+                })
+                .unwrap();
+        }
+        // Comma/Period nudge its Q.
+        Key::Comma => {
+            model
+                .stream
+                .send(|audio| {
+                    audio.eq.nudge_q(-EQ_Q_STEP);
+                    audio.publish_eq();
+                })
+                .unwrap();
+        }
+        Key::Period => {
+            model
+                .stream
+                .send(|audio| {
+                    audio.eq.nudge_q(EQ_Q_STEP);
+                    audio.publish_eq();
+                })
+                .unwrap();
+        }
+        // Semicolon/Apostrophe nudge its gain (only audible on Bell).
+        Key::Semicolon => {
+            model
+                .stream
+                .send(|audio| {
+                    audio.eq.nudge_gain(-EQ_GAIN_STEP);
+                    audio.publish_eq();
+                })
+                .unwrap();
+        }
+        Key::Apostrophe => {
+            model
+                .stream
+                .send(|audio| {
+                    audio.eq.nudge_gain(EQ_GAIN_STEP);
+                    audio.publish_eq();
                 })
                 .unwrap();
         }
@@ -188,22 +1083,61 @@ fn key_pressed(_app: &App, model: &mut Model, key: Key) {
     }
 }
 
+// Ends the Bowed excitation's sustain and starts its release; ignored by
+// the one-shot modes.
+fn key_released(_app: &App, model: &mut Model, key: Key) {
+    if key == Key::A {
+        model.producer_tx.send(ProducerMsg::ExcitationKeyUp).unwrap();
+    }
+}
+
+// Number of bars in the EQ response plot and the log-swept frequency
+// range they cover.
+const RESPONSE_BARS: usize = 64;
+const RESPONSE_MIN_HZ: f64 = 20.0;
+const RESPONSE_MAX_HZ: f64 = 20_000.0;
+
 fn view(app: &App, model: &Model, frame: Frame) {
     let r = frame.rect();
     frame.clear(DIMGRAY);
     let draw = app.draw();
     let cell_width = 500.0 / (CELL_COUNT as f32);
     for i in 0..CELL_COUNT {
-        let pressure = model.chamber.cells.cur[i];
+        let pressure = model.view_cells[i];
         draw.quad()
             .w_h(cell_width, 30.0)
             .x_y(((i as f32 * cell_width) as f32) - 250.0, 100.0)
             .color(Gray::new(pressure + 0.5, pressure + 0.5, pressure + 0.5));
     }
+
+    // EQ magnitude response, 20 Hz to 20 kHz on a log axis, as a bar
+    // graph below the chamber's pressure row.
+    let bands = *model.eq_snapshot.lock().unwrap();
+    let bar_width = 500.0 / RESPONSE_BARS as f32;
+    let sweep = (RESPONSE_MAX_HZ / RESPONSE_MIN_HZ).ln();
+    for i in 0..RESPONSE_BARS {
+        let t = i as f64 / (RESPONSE_BARS - 1) as f64;
+        let freq = RESPONSE_MIN_HZ * (t * sweep).exp();
+        let mag: f64 = bands
+            .iter()
+            .map(|band| {
+                let (b0, b1, b2, a1, a2) = rbj_coeffs(*band, SAMPLE_RATE);
+                biquad_magnitude(b0, b1, b2, a1, a2, freq, SAMPLE_RATE)
+            })
+            .product();
+        let db = 20.0 * mag.max(1e-6).log10();
+        let height = ((db + 24.0) * 3.0).clamp(1.0, 150.0) as f32;
+        draw.rect()
+            .w_h(bar_width * 0.8, height)
+            .x_y((i as f32 * bar_width) - 250.0, -150.0 + height / 2.0)
+            .color(STEELBLUE);
+    }
+
     draw.to_frame(app, &frame).unwrap();
 }
 
 fn update(_app: &App, model: &mut Model, _update: Update) {
-    // model.chamber.add_pressure(0.01);
-    model.chamber.update_pressures();
+    if let Ok(snapshot) = model.snapshot.lock() {
+        model.view_cells = *snapshot;
+    }
 }